@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
 use std::fs::File;
@@ -19,6 +20,12 @@ struct HelperClassStats {
     instance_count: u64,
     largest_allocation_bytes: u64,
     allocation_size_bytes: u64,
+    // Only populated when the helper can walk `JavaClass.getSuperclass()`; absent (rather than
+    // an error) for formats where DTFJ can't resolve the class hierarchy.
+    #[serde(default)]
+    super_class_name: Option<String>,
+    #[serde(default)]
+    class_loader: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -30,6 +37,36 @@ struct HelperResponse {
     string_count: u64,
     total_heap_bytes: u64,
     format: String,
+    #[serde(default)]
+    threads: Vec<HelperThread>,
+}
+
+#[derive(Deserialize)]
+struct HelperThread {
+    name: String,
+    state: String,
+    frames: Vec<HelperFrame>,
+}
+
+#[derive(Deserialize)]
+struct HelperFrame {
+    declaring_class: String,
+    method_name: String,
+    source_file: Option<String>,
+    line_number: Option<i32>,
+    is_native: bool,
+}
+
+/// The helper emits a frame with this class name when it hit a `CorruptDataException` resolving
+/// an individual `JavaStackFrame` — one bad frame doesn't abort the whole thread's listing.
+const CORRUPT_FRAME_MARKER: &str = "<corrupt frame>";
+
+/// A JDK installation qualified for running the helper: both `java` and `javac` exist and the
+/// major version is at least 9, the floor for the `--add-exports jdk.internal.org.objectweb.asm*`
+/// flags `invoke_helper`/`compile_helper` pass.
+struct JdkInstallation {
+    java: PathBuf,
+    javac: PathBuf,
 }
 
 struct DtfjClasspath {
@@ -50,15 +87,91 @@ impl DtfjClasspath {
     }
 }
 
+const DTFJ_ADD_EXPORTS: &[&str] = &[
+    "--add-exports",
+    "java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED",
+    "--add-exports",
+    "java.base/jdk.internal.org.objectweb.asm.tree=ALL-UNNAMED",
+    "--add-exports",
+    "java.base/jdk.internal.module=ALL-UNNAMED",
+];
+
 pub fn analyze_with_java_helper(
     format_label: &str,
     dump_path: &str,
     file_len: u64,
     list_strings: bool,
 ) -> Result<RenderedResult, HprofSlurpError> {
+    let jdk = locate_jdk()?;
     let dtfj = locate_dtfj_jars()?;
-    let class_dir = compile_helper(&dtfj)?;
-    let response = invoke_helper(&class_dir, &dtfj, format_label, dump_path)?;
+    let class_dir = compile_helper(&jdk, &dtfj.all(), &[])?;
+    let response = invoke_helper(
+        &jdk,
+        &class_dir,
+        &dtfj.all(),
+        DTFJ_ADD_EXPORTS,
+        &["--input", dump_path, "--format", format_label],
+    )?;
+
+    Ok(render_helper_response(
+        response,
+        format_label,
+        file_len,
+        list_strings,
+    ))
+}
+
+/// Analyzes a HotSpot (OpenJDK/Oracle) core file through the JDK's bundled Serviceability Agent
+/// instead of DTFJ. SA requires the exact `java` executable that produced the core, which is why
+/// `executable_path` is a required argument rather than something this function can infer.
+pub fn analyze_hotspot_core_with_java_helper(
+    executable_path: &str,
+    core_path: &str,
+    file_len: u64,
+    list_strings: bool,
+) -> Result<RenderedResult, HprofSlurpError> {
+    analyze_with_serviceability_agent(
+        "hotspot-core",
+        &["--executable", executable_path, "--core", core_path],
+        file_len,
+        list_strings,
+    )
+}
+
+/// Analyzes a live HotSpot process through the Serviceability Agent by attaching to its pid.
+pub fn analyze_hotspot_pid_with_java_helper(
+    pid: u32,
+    list_strings: bool,
+) -> Result<RenderedResult, HprofSlurpError> {
+    let pid_arg = pid.to_string();
+    analyze_with_serviceability_agent("hotspot-pid", &["--pid", &pid_arg], 0, list_strings)
+}
+
+fn analyze_with_serviceability_agent(
+    format_label: &str,
+    target_args: &[&str],
+    file_len: u64,
+    list_strings: bool,
+) -> Result<RenderedResult, HprofSlurpError> {
+    // SA attach is not reentrant: the JVM-internal structures it walks assume a single attached
+    // client, so two concurrent attaches (even to different targets) can corrupt each other's
+    // view or hang. Serialize every SA invocation made by this process.
+    static SA_ATTACH_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let _guard = SA_ATTACH_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let jdk = locate_jdk()?;
+    let sa = locate_sa_support(&jdk)?;
+    let add_exports = sa.add_exports_args();
+    let classpath = sa.classpath();
+
+    let class_dir = compile_helper(&jdk, &classpath, &add_exports)?;
+
+    let mut program_args = vec!["--format", format_label];
+    program_args.extend_from_slice(target_args);
+
+    let response = invoke_helper(&jdk, &class_dir, &classpath, &add_exports, &program_args)?;
 
     Ok(render_helper_response(
         response,
@@ -68,12 +181,210 @@ pub fn analyze_with_java_helper(
     ))
 }
 
+/// Where the JDK's bundled Serviceability Agent classes live. Older (early JDK 9 era) layouts
+/// ship a standalone `lib/sa-jdi.jar`; current JDKs fold SA into the `jdk.hotspot.agent` module
+/// instead, reachable purely through `--add-modules`/`--add-exports` with no extra classpath
+/// entry required.
+enum SaSupport {
+    Jar(PathBuf),
+    Module,
+}
+
+impl SaSupport {
+    fn classpath(&self) -> Vec<&Path> {
+        match self {
+            SaSupport::Jar(path) => vec![path.as_path()],
+            SaSupport::Module => Vec::new(),
+        }
+    }
+
+    fn add_exports_args(&self) -> Vec<&str> {
+        let mut args = Vec::new();
+        if matches!(self, SaSupport::Module) {
+            args.extend(["--add-modules", "jdk.hotspot.agent"]);
+        }
+        args.extend([
+            "--add-exports",
+            "jdk.hotspot.agent/sun.jvm.hotspot=ALL-UNNAMED",
+            "--add-exports",
+            "jdk.hotspot.agent/sun.jvm.hotspot.oops=ALL-UNNAMED",
+            "--add-exports",
+            "jdk.hotspot.agent/sun.jvm.hotspot.runtime=ALL-UNNAMED",
+            "--add-exports",
+            "jdk.hotspot.agent/sun.jvm.hotspot.memory=ALL-UNNAMED",
+        ]);
+        args
+    }
+}
+
+/// `jdk.java` is `<home>/bin/java`, so its grandparent is the JDK home both layouts live under.
+fn locate_sa_support(jdk: &JdkInstallation) -> Result<SaSupport, HprofSlurpError> {
+    let Some(home) = jdk.java.parent().and_then(Path::parent) else {
+        return Err(JavaHelperError {
+            message: format!(
+                "Unable to determine the JDK home from {}",
+                jdk.java.display()
+            ),
+        });
+    };
+
+    let jmod = home.join("jmods").join("jdk.hotspot.agent.jmod");
+    if jmod.exists() {
+        return Ok(SaSupport::Module);
+    }
+
+    let jar = home.join("lib").join("sa-jdi.jar");
+    if jar.exists() {
+        return Ok(SaSupport::Jar(jar));
+    }
+
+    Err(JavaHelperError {
+        message: format!(
+            "Unable to locate the Serviceability Agent in JDK at {}: expected jmods/jdk.hotspot.agent.jmod or lib/sa-jdi.jar",
+            home.display()
+        ),
+    })
+}
+
+/// Locates a JDK (not just a JRE) with a `javac` alongside `java`, searching `JAVA_HOME` and then
+/// the platform-standard install roots, and rejects anything below JDK 9 — the version that
+/// introduced the module system the `--add-exports` flags in [`invoke_helper`] depend on. This
+/// mirrors [`locate_dtfj_jars`]'s directory-search-with-clear-error shape.
+fn locate_jdk() -> Result<JdkInstallation, HprofSlurpError> {
+    let mut searched = Vec::new();
+
+    let mut candidate_homes = Vec::new();
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        candidate_homes.push(PathBuf::from(java_home));
+    }
+    candidate_homes.extend(platform_default_jdk_roots());
+
+    for home in candidate_homes {
+        searched.push(home.display().to_string());
+        if let Some(jdk) = try_qualify_jdk_home(&home)? {
+            return Ok(jdk);
+        }
+    }
+
+    Err(JavaHelperError {
+        message: format!(
+            "Unable to locate a JDK 9+ installation. Set JAVA_HOME, or install one under: {}",
+            searched.join(", ")
+        ),
+    })
+}
+
+fn platform_default_jdk_roots() -> Vec<PathBuf> {
+    if cfg!(target_os = "macos") {
+        glob_direct_children(Path::new("/Library/Java/JavaVirtualMachines"))
+            .into_iter()
+            .map(|dir| dir.join("Contents/Home"))
+            .collect()
+    } else if cfg!(windows) {
+        let mut roots = glob_direct_children(Path::new("C:\\Program Files\\Java"));
+        roots.extend(glob_direct_children(Path::new(
+            "C:\\Program Files\\Eclipse Adoptium",
+        )));
+        roots
+    } else {
+        glob_direct_children(Path::new("/usr/lib/jvm"))
+    }
+}
+
+fn glob_direct_children(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn try_qualify_jdk_home(home: &Path) -> Result<Option<JdkInstallation>, HprofSlurpError> {
+    let exe_suffix = if cfg!(windows) { ".exe" } else { "" };
+    let java = home.join("bin").join(format!("java{exe_suffix}"));
+    let javac = home.join("bin").join(format!("javac{exe_suffix}"));
+
+    if !java.is_file() || !javac.is_file() {
+        return Ok(None);
+    }
+
+    let version = resolve_major_version(home, &java)?;
+    if version < 9 {
+        return Ok(None);
+    }
+
+    Ok(Some(JdkInstallation { java, javac }))
+}
+
+/// Prefers the `release` file's `JAVA_VERSION` (no process spawn needed); falls back to parsing
+/// `java -version`'s stderr banner when `release` is missing or unparseable, which happens for
+/// some vendor-repackaged JDKs.
+fn resolve_major_version(home: &Path, java: &Path) -> Result<u32, HprofSlurpError> {
+    if let Some(version) = fs::read_to_string(home.join("release"))
+        .ok()
+        .and_then(|contents| parse_release_file_version(&contents))
+    {
+        return Ok(version);
+    }
+
+    let output = Command::new(java)
+        .arg("-version")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| JavaHelperError {
+            message: format!("Failed to run {}: {e}", java.display()),
+        })?;
+
+    let banner = String::from_utf8_lossy(&output.stderr);
+    parse_java_version_banner(&banner).ok_or_else(|| JavaHelperError {
+        message: format!(
+            "Unable to determine the Java version of {}: {}",
+            java.display(),
+            banner.trim()
+        ),
+    })
+}
+
+fn parse_release_file_version(contents: &str) -> Option<u32> {
+    let line = contents
+        .lines()
+        .find(|line| line.starts_with("JAVA_VERSION="))?;
+    let quoted = line.trim_start_matches("JAVA_VERSION=").trim_matches('"');
+    parse_major_version(quoted)
+}
+
+fn parse_java_version_banner(banner: &str) -> Option<u32> {
+    let first_line = banner.lines().next()?;
+    let quoted = first_line.split('"').nth(1)?;
+    parse_major_version(quoted)
+}
+
+/// Handles both the pre-JDK-9 `1.8.0_392` scheme and the post-JDK-9 `17.0.2` scheme.
+fn parse_major_version(version: &str) -> Option<u32> {
+    let mut parts = version.split('.');
+    let first: u32 = parts.next()?.parse().ok()?;
+    if first == 1 {
+        parts.next()?.parse().ok()
+    } else {
+        Some(first)
+    }
+}
+
 fn render_helper_response(
     response: HelperResponse,
     format_label: &str,
     file_len: u64,
     list_strings: bool,
 ) -> RenderedResult {
+    // Computed from `response.memory_usage` before it's moved out below to build the
+    // `ClassAllocationStats` list.
+    let aggregated_memory = render_aggregated_memory(&response.memory_usage);
+
     let memory_usage = response
         .memory_usage
         .into_iter()
@@ -96,12 +407,9 @@ fn render_helper_response(
         strings = response.string_count,
         heap_bytes = response.total_heap_bytes,
     );
+    let summary = format!("{summary}\n{}", aggregated_memory);
 
-    let thread_info = formatdoc!(
-        "\nThread information:\n  Threads discovered: {threads}\n  Detailed stack traces are not available for {format_label} dumps in helper mode.",
-        threads = response.thread_count,
-        format_label = format_label,
-    );
+    let thread_info = render_thread_info(response.thread_count, &response.threads, format_label);
 
     let captured_strings = if list_strings {
         Some("Listing captured strings is not yet supported for this dump format.\n".to_string())
@@ -118,34 +426,191 @@ fn render_helper_response(
     }
 }
 
+/// One node of an aggregated subtree (a package prefix or a superclass), summing the
+/// `instance_count`/`allocation_size_bytes` of every class rolled up underneath it.
+struct MemoryRollup {
+    label: String,
+    instance_count: u64,
+    allocation_size_bytes: u64,
+}
+
+const TOP_ROLLUPS_SHOWN: usize = 10;
+
+/// Renders the top package and inheritance subtrees by aggregated allocation size, so memory can
+/// be attributed to a base class or a package prefix rather than only to leaf classes.
+fn render_aggregated_memory(classes: &[HelperClassStats]) -> String {
+    let mut out = formatdoc!(
+        "\nAggregated memory by package (top {top}):",
+        top = TOP_ROLLUPS_SHOWN,
+    );
+    for rollup in package_rollups(classes).into_iter().take(TOP_ROLLUPS_SHOWN) {
+        out.push_str(&format!(
+            "\n  {:<60} instances={:<10} bytes={}",
+            rollup.label, rollup.instance_count, rollup.allocation_size_bytes
+        ));
+    }
+
+    out.push_str(&formatdoc!(
+        "\n\nAggregated memory by superclass (top {top}):",
+        top = TOP_ROLLUPS_SHOWN,
+    ));
+    for rollup in inheritance_rollups(classes)
+        .into_iter()
+        .take(TOP_ROLLUPS_SHOWN)
+    {
+        out.push_str(&format!(
+            "\n  {:<60} instances={:<10} bytes={}",
+            rollup.label, rollup.instance_count, rollup.allocation_size_bytes
+        ));
+    }
+
+    out
+}
+
+/// Sums every class's stats into each of its dotted-name prefixes, e.g. `com.example.Foo` rolls
+/// up into `com` and `com.example`, so a whole package's footprint is visible without listing
+/// every leaf class underneath it.
+fn package_rollups(classes: &[HelperClassStats]) -> Vec<MemoryRollup> {
+    let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+    for class in classes {
+        let segments: Vec<&str> = class.class_name.split('.').collect();
+        for prefix_len in 1..segments.len() {
+            let prefix = segments[..prefix_len].join(".");
+            let entry = totals.entry(prefix).or_insert((0, 0));
+            entry.0 += class.instance_count;
+            entry.1 += class.allocation_size_bytes;
+        }
+    }
+    sorted_rollups(totals)
+}
+
+/// Sums every class's stats into each ancestor along its superclass chain, so e.g. every
+/// `java.util.AbstractMap` subclass's instances are attributed to `java.util.AbstractMap`. Classes
+/// are looked up by `(name, defining classloader)` rather than name alone, since two loaders are
+/// free to define distinct classes sharing a simple name — keying on name only would silently
+/// walk one class's instances up the other's superclass chain. The immediate superclass is always
+/// credited; beyond it the chain is followed only through ancestors that have an entry of their
+/// own in `classes` under the starting class's loader, and the walk stops at the first ancestor
+/// without one (e.g. an abstract class with no live instances, or a class defined by a different
+/// loader), so ancestors above that point are not credited. Guards against a superclass cycle
+/// reported by a buggy or adversarial helper by tracking visited classes per starting class.
+fn inheritance_rollups(classes: &[HelperClassStats]) -> Vec<MemoryRollup> {
+    let by_identity: HashMap<(&str, Option<&str>), &HelperClassStats> = classes
+        .iter()
+        .map(|class| {
+            (
+                (class.class_name.as_str(), class.class_loader.as_deref()),
+                class,
+            )
+        })
+        .collect();
+
+    let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+    for class in classes {
+        let mut visited = HashSet::new();
+        let mut current = class
+            .super_class_name
+            .as_deref()
+            .map(|name| (name, class.class_loader.as_deref()));
+        while let Some((super_name, loader)) = current {
+            if !visited.insert((super_name, loader)) {
+                break;
+            }
+            let entry = totals.entry(super_name.to_string()).or_insert((0, 0));
+            entry.0 += class.instance_count;
+            entry.1 += class.allocation_size_bytes;
+            current = by_identity
+                .get(&(super_name, loader))
+                .and_then(|c| c.super_class_name.as_deref())
+                .map(|name| (name, loader));
+        }
+    }
+    sorted_rollups(totals)
+}
+
+fn sorted_rollups(totals: HashMap<String, (u64, u64)>) -> Vec<MemoryRollup> {
+    let mut rollups: Vec<MemoryRollup> = totals
+        .into_iter()
+        .map(
+            |(label, (instance_count, allocation_size_bytes))| MemoryRollup {
+                label,
+                instance_count,
+                allocation_size_bytes,
+            },
+        )
+        .collect();
+    rollups.sort_by(|a, b| b.allocation_size_bytes.cmp(&a.allocation_size_bytes));
+    rollups
+}
+
+/// Renders an indented, per-thread stack listing in the style of HotSpot's own thread dumps.
+/// Falls back to the old one-line summary when the helper reports no frames at all, which is
+/// still the case for dump formats DTFJ can't walk stacks for.
+///
+/// Takes `thread_count`/`threads` rather than the whole `HelperResponse` so the caller can still
+/// read them after `response.memory_usage` has been moved out to build the rendered class list.
+fn render_thread_info(thread_count: usize, threads: &[HelperThread], format_label: &str) -> String {
+    if threads.iter().all(|thread| thread.frames.is_empty()) {
+        return formatdoc!(
+            "\nThread information:\n  Threads discovered: {threads}\n  Detailed stack traces are not available for {format_label} dumps in helper mode.",
+            threads = thread_count,
+            format_label = format_label,
+        );
+    }
+
+    let mut out = formatdoc!(
+        "\nThread information:\n  Threads discovered: {threads}\n",
+        threads = thread_count,
+    );
+
+    for thread in threads {
+        out.push_str(&format!("\n\"{}\" {}\n", thread.name, thread.state));
+        if thread.frames.is_empty() {
+            out.push_str("    <no frames captured>\n");
+            continue;
+        }
+        for frame in &thread.frames {
+            out.push_str(&format!("    at {}\n", render_frame(frame)));
+        }
+    }
+
+    out
+}
+
+fn render_frame(frame: &HelperFrame) -> String {
+    if frame.declaring_class == CORRUPT_FRAME_MARKER {
+        return CORRUPT_FRAME_MARKER.to_string();
+    }
+    let location = match (&frame.source_file, frame.line_number) {
+        (Some(source_file), Some(line)) => format!("{source_file}:{line}"),
+        (Some(source_file), None) => source_file.clone(),
+        (None, _) if frame.is_native => "Native Method".to_string(),
+        (None, _) => "Unknown Source".to_string(),
+    };
+    format!(
+        "{}.{}({})",
+        frame.declaring_class, frame.method_name, location
+    )
+}
+
 fn invoke_helper(
+    jdk: &JdkInstallation,
     class_dir: &Path,
-    dtfj: &DtfjClasspath,
-    format_label: &str,
-    dump_path: &str,
+    extra_classpath: &[&Path],
+    extra_jvm_args: &[&str],
+    program_args: &[&str],
 ) -> Result<HelperResponse, HprofSlurpError> {
-    let mut parts = dtfj.all();
-    let mut classpath_parts = Vec::with_capacity(parts.len() + 1);
+    let mut classpath_parts = Vec::with_capacity(extra_classpath.len() + 1);
     classpath_parts.push(class_dir);
-    classpath_parts.append(&mut parts);
+    classpath_parts.extend_from_slice(extra_classpath);
     let classpath = join_classpath(&classpath_parts);
 
-    let output = Command::new("java")
-        .args([
-            "--add-exports",
-            "java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED",
-            "--add-exports",
-            "java.base/jdk.internal.org.objectweb.asm.tree=ALL-UNNAMED",
-            "--add-exports",
-            "java.base/jdk.internal.module=ALL-UNNAMED",
-        ])
+    let output = Command::new(&jdk.java)
+        .args(extra_jvm_args)
         .arg("-cp")
         .arg(classpath)
         .arg("com.maisi.heapdump.JavaHeapAnalyzer")
-        .arg("--input")
-        .arg(dump_path)
-        .arg("--format")
-        .arg(format_label)
+        .args(program_args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
@@ -169,7 +634,11 @@ fn invoke_helper(
     })
 }
 
-fn compile_helper(dtfj: &DtfjClasspath) -> Result<PathBuf, HprofSlurpError> {
+fn compile_helper(
+    jdk: &JdkInstallation,
+    extra_classpath: &[&Path],
+    extra_javac_args: &[&str],
+) -> Result<PathBuf, HprofSlurpError> {
     let helper_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("java-helper");
     let src_dir = helper_root.join("src");
     let class_dir = helper_root.join("target/classes");
@@ -186,9 +655,9 @@ fn compile_helper(dtfj: &DtfjClasspath) -> Result<PathBuf, HprofSlurpError> {
         });
     }
 
-    let dtfj_classpath = dtfj.all();
-    let classpath = join_classpath(&dtfj_classpath);
-    let output = Command::new("javac")
+    let classpath = join_classpath(extra_classpath);
+    let output = Command::new(&jdk.javac)
+        .args(extra_javac_args)
         .arg("-cp")
         .arg(classpath)
         .arg("-d")
@@ -533,4 +1002,150 @@ mod tests {
         ));
         assert!(!is_dtfj_interface_jar("dtfj-interface.txt"));
     }
+
+    #[test]
+    fn parses_modern_and_legacy_version_schemes() {
+        assert_eq!(parse_major_version("17.0.2"), Some(17));
+        assert_eq!(parse_major_version("9"), Some(9));
+        assert_eq!(parse_major_version("1.8.0_392"), Some(8));
+    }
+
+    #[test]
+    fn parses_release_file_version() {
+        let release = "JAVA_VERSION=\"21.0.1\"\nOS_NAME=\"Linux\"\n";
+        assert_eq!(parse_release_file_version(release), Some(21));
+        assert_eq!(parse_release_file_version("OS_NAME=\"Linux\"\n"), None);
+    }
+
+    #[test]
+    fn parses_java_version_banner() {
+        let banner = "openjdk version \"17.0.9\" 2023-10-17\nOpenJDK Runtime Environment\n";
+        assert_eq!(parse_java_version_banner(banner), Some(17));
+    }
+
+    #[test]
+    fn thread_info_falls_back_when_no_frames_reported() {
+        let response = HelperResponse {
+            memory_usage: Vec::new(),
+            total_objects: 0,
+            class_count: 0,
+            thread_count: 3,
+            string_count: 0,
+            total_heap_bytes: 0,
+            format: "phd".to_string(),
+            threads: Vec::new(),
+        };
+        let info = render_thread_info(response.thread_count, &response.threads, "phd");
+        assert!(info.contains("Threads discovered: 3"));
+        assert!(info.contains("not available for phd dumps"));
+    }
+
+    #[test]
+    fn renders_frame_with_source_and_line() {
+        let frame = HelperFrame {
+            declaring_class: "com.example.Foo".to_string(),
+            method_name: "bar".to_string(),
+            source_file: Some("Foo.java".to_string()),
+            line_number: Some(42),
+            is_native: false,
+        };
+        assert_eq!(render_frame(&frame), "com.example.Foo.bar(Foo.java:42)");
+    }
+
+    #[test]
+    fn renders_corrupt_frame_marker_as_is() {
+        let frame = HelperFrame {
+            declaring_class: CORRUPT_FRAME_MARKER.to_string(),
+            method_name: "unused".to_string(),
+            source_file: None,
+            line_number: None,
+            is_native: false,
+        };
+        assert_eq!(render_frame(&frame), CORRUPT_FRAME_MARKER);
+    }
+
+    fn class_stats(
+        class_name: &str,
+        instance_count: u64,
+        allocation_size_bytes: u64,
+        super_class_name: Option<&str>,
+    ) -> HelperClassStats {
+        HelperClassStats {
+            class_name: class_name.to_string(),
+            instance_count,
+            largest_allocation_bytes: allocation_size_bytes,
+            allocation_size_bytes,
+            super_class_name: super_class_name.map(str::to_string),
+            class_loader: None,
+        }
+    }
+
+    #[test]
+    fn package_rollups_sum_every_ancestor_prefix() {
+        let classes = vec![
+            class_stats("com.example.foo.Foo", 10, 1_000, None),
+            class_stats("com.example.bar.Bar", 5, 500, None),
+        ];
+
+        let rollups = package_rollups(&classes);
+        let com = rollups.iter().find(|r| r.label == "com").unwrap();
+        assert_eq!(com.instance_count, 15);
+        assert_eq!(com.allocation_size_bytes, 1_500);
+
+        let com_example_foo = rollups
+            .iter()
+            .find(|r| r.label == "com.example.foo")
+            .unwrap();
+        assert_eq!(com_example_foo.instance_count, 10);
+        assert_eq!(com_example_foo.allocation_size_bytes, 1_000);
+    }
+
+    #[test]
+    fn inheritance_rollups_attribute_subclasses_to_every_ancestor() {
+        let classes = vec![
+            class_stats(
+                "java.util.HashMap",
+                100,
+                10_000,
+                Some("java.util.AbstractMap"),
+            ),
+            class_stats("java.util.AbstractMap", 0, 0, Some("java.lang.Object")),
+            class_stats(
+                "java.util.TreeMap",
+                20,
+                2_000,
+                Some("java.util.AbstractMap"),
+            ),
+        ];
+
+        let rollups = inheritance_rollups(&classes);
+        let abstract_map = rollups
+            .iter()
+            .find(|r| r.label == "java.util.AbstractMap")
+            .unwrap();
+        assert_eq!(abstract_map.instance_count, 120);
+        assert_eq!(abstract_map.allocation_size_bytes, 12_000);
+
+        let object = rollups
+            .iter()
+            .find(|r| r.label == "java.lang.Object")
+            .unwrap();
+        assert_eq!(object.instance_count, 120);
+        assert_eq!(object.allocation_size_bytes, 12_000);
+    }
+
+    #[test]
+    fn inheritance_rollups_ignore_a_superclass_cycle() {
+        let classes = vec![
+            class_stats("com.example.A", 3, 300, Some("com.example.B")),
+            class_stats("com.example.B", 0, 0, Some("com.example.A")),
+        ];
+
+        // A cycle must not hang the walk; each ancestor is still credited once per starting class.
+        let rollups = inheritance_rollups(&classes);
+        let a = rollups.iter().find(|r| r.label == "com.example.A").unwrap();
+        assert_eq!(a.instance_count, 3);
+        let b = rollups.iter().find(|r| r.label == "com.example.B").unwrap();
+        assert_eq!(b.instance_count, 3);
+    }
 }
@@ -0,0 +1,145 @@
+//! Pluggable output serializers for [`RenderedResult`].
+//!
+//! `RenderedResult::serialize` only ever produced the fixed human-readable top-N text report
+//! validated by the gold-file tests in `slurp.rs`, which is awkward to pipe into a dashboard or
+//! diff between two dumps programmatically. [`RenderedResultSerialize::serialize_as`] adds
+//! structured alternatives (JSON, CSV) selected by [`OutputFormat`], while leaving the text
+//! rendering as the default.
+
+use crate::rendered_result::RenderedResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Renders a [`RenderedResult`] in a caller-selected [`OutputFormat`]. `Text` defers to the
+/// existing `RenderedResult::serialize`, which remains the default and the one checked against
+/// the gold files.
+pub trait RenderedResultSerialize {
+    fn serialize_as(&self, top_n: usize, format: OutputFormat) -> String;
+}
+
+impl RenderedResultSerialize for RenderedResult {
+    fn serialize_as(&self, top_n: usize, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.serialize(top_n),
+            OutputFormat::Json => serialize_json(self, top_n),
+            OutputFormat::Csv => serialize_csv(self, top_n),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct JsonClassEntry<'a> {
+    class_name: &'a str,
+    instance_count: u64,
+    largest_allocation_bytes: u64,
+    allocation_size_bytes: u64,
+}
+
+#[derive(serde::Serialize)]
+struct JsonReport<'a> {
+    summary: &'a str,
+    thread_info: &'a str,
+    top_classes: Vec<JsonClassEntry<'a>>,
+    captured_strings: Option<&'a str>,
+}
+
+fn top_classes_by_size(
+    result: &RenderedResult,
+    top_n: usize,
+) -> Vec<&crate::rendered_result::ClassAllocationStats> {
+    let mut sorted: Vec<_> = result.memory_usage.iter().collect();
+    sorted.sort_by(|a, b| b.allocation_size_bytes.cmp(&a.allocation_size_bytes));
+    sorted.truncate(top_n);
+    sorted
+}
+
+fn serialize_json(result: &RenderedResult, top_n: usize) -> String {
+    let report = JsonReport {
+        summary: &result.summary,
+        thread_info: &result.thread_info,
+        top_classes: top_classes_by_size(result, top_n)
+            .into_iter()
+            .map(|entry| JsonClassEntry {
+                class_name: &entry.class_name,
+                instance_count: entry.instance_count,
+                largest_allocation_bytes: entry.largest_allocation_bytes,
+                allocation_size_bytes: entry.allocation_size_bytes,
+            })
+            .collect(),
+        captured_strings: result.captured_strings.as_deref(),
+    };
+
+    serde_json::to_string_pretty(&report).expect("RenderedResult always serializes to valid JSON")
+}
+
+fn serialize_csv(result: &RenderedResult, top_n: usize) -> String {
+    let mut out =
+        String::from("class_name,instance_count,largest_allocation_bytes,allocation_size_bytes\n");
+    for entry in top_classes_by_size(result, top_n) {
+        out.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_escape(&entry.class_name),
+            entry.instance_count,
+            entry.largest_allocation_bytes,
+            entry.allocation_size_bytes,
+        ));
+    }
+    out
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendered_result::ClassAllocationStats;
+
+    fn sample_result() -> RenderedResult {
+        RenderedResult {
+            summary: "summary".to_string(),
+            thread_info: "threads".to_string(),
+            memory_usage: vec![ClassAllocationStats::new(
+                "com.example.Foo".to_string(),
+                10,
+                128,
+                1280,
+            )],
+            duplicated_strings: None,
+            captured_strings: None,
+        }
+    }
+
+    #[test]
+    fn json_round_trips_top_class_fields() {
+        let result = sample_result();
+        let json = result.serialize_as(10, OutputFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["top_classes"][0]["class_name"], "com.example.Foo");
+        assert_eq!(value["top_classes"][0]["instance_count"], 10);
+        assert_eq!(value["top_classes"][0]["allocation_size_bytes"], 1280);
+    }
+
+    #[test]
+    fn csv_emits_header_then_one_row_per_class() {
+        let result = sample_result();
+        let csv = result.serialize_as(10, OutputFormat::Csv);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "class_name,instance_count,largest_allocation_bytes,allocation_size_bytes"
+        );
+        assert_eq!(lines.next().unwrap(), "com.example.Foo,10,128,1280");
+        assert_eq!(lines.next(), None);
+    }
+}
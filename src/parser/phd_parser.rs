@@ -0,0 +1,410 @@
+//! Native reader for the IBM "Portable Heap Dump" (PHD) format, used by OpenJ9 and older IBM
+//! JVMs. Historically these dumps were handed off to [`crate::java_bridge::analyze_with_java_helper`],
+//! which requires a JVM plus the DTFJ jars on the machine running this tool. This module parses
+//! the tagged PHD record stream directly, the same way [`crate::parser::record_stream_parser`]
+//! walks the hprof binary stream, and feeds the result into the same [`ResultRecorder`] pipeline.
+//!
+//! The format after the `"portable heap dump"` header is a flat sequence of tagged records: a
+//! one-byte tag identifies the record kind, and the fields that follow are read by that kind's
+//! fixed layout (variable-length parts are sized by counts carried in the record). A record
+//! carries no overall length, so an unknown tag can't be skipped: parsing stops with
+//! `HprofSlurpError::UnsupportedPhdFeature`, which callers treat as the signal to fall back to
+//! the Java helper.
+
+use std::io::{self, BufRead, Read};
+
+use crate::errors::HprofSlurpError;
+use crate::errors::HprofSlurpError::{InvalidHprofFile, UnsupportedPhdFeature};
+use crate::parser::record::Record;
+use crate::rendered_result::RenderedResult;
+use crate::result_recorder::ResultRecorder;
+
+const PHD_MAGIC: &[u8] = b"portable heap dump";
+
+// The header flags word packs the address width into bit 0 and the format version into the next
+// byte. Versions above this one are free to change the record layout in ways this parser doesn't
+// understand, so they fall back to the Java helper rather than risk misreading the stream.
+const PHD_MAX_SUPPORTED_VERSION: u8 = 5;
+
+// Record tags, per the PHD tagged-record layout.
+const TAG_CLASS: u8 = 1;
+const TAG_OBJECT: u8 = 2;
+const TAG_OBJECT_ARRAY: u8 = 3;
+const TAG_PRIMITIVE_ARRAY: u8 = 4;
+const TAG_END_OF_DUMP: u8 = 0xFF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AddressWidth {
+    Bits32,
+    Bits64,
+}
+
+impl AddressWidth {
+    fn byte_len(self) -> usize {
+        match self {
+            AddressWidth::Bits32 => 4,
+            AddressWidth::Bits64 => 8,
+        }
+    }
+}
+
+struct ClassInfo {
+    name: String,
+    instance_size: u32,
+}
+
+/// Parses a PHD dump from `reader` and renders it through the usual class-allocation summary,
+/// the same shape the Java-helper path produces.
+pub fn parse_phd_dump<R: BufRead>(
+    mut reader: R,
+    list_strings: bool,
+) -> Result<RenderedResult, HprofSlurpError> {
+    let address_width = read_phd_header(&mut reader)?;
+
+    let mut recorder = ResultRecorder::new(address_width.byte_len() as u8, list_strings);
+
+    loop {
+        let mut tag_buf = [0u8; 1];
+        match reader.read_exact(&mut tag_buf) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+
+        match tag_buf[0] {
+            TAG_END_OF_DUMP => break,
+            TAG_CLASS => {
+                let (address, info) = read_class_record(&mut reader, address_width)?;
+                recorder.record(vec![Record::ClassDump {
+                    class_object_id: address,
+                    class_name: info.name,
+                    instance_size: info.instance_size as u64,
+                }])?;
+            }
+            TAG_OBJECT => {
+                let class_address = read_object_record(&mut reader, address_width)?;
+                recorder.record(vec![Record::InstanceAllocation {
+                    class_object_id: class_address,
+                }])?;
+            }
+            TAG_OBJECT_ARRAY | TAG_PRIMITIVE_ARRAY => {
+                let (class_address, size_bytes) = read_array_record(&mut reader, address_width)?;
+                recorder.record(vec![Record::ArrayAllocation {
+                    class_object_id: class_address,
+                    size_bytes,
+                }])?;
+            }
+            unknown => {
+                // An unrecognized tag ends native parsing rather than being skipped: there is
+                // nothing declaring its length once the tag itself is unknown, so we can't
+                // safely resynchronize — bail out to the Java helper instead of guessing at a
+                // skip distance. This is distinct from
+                // `InvalidHprofFile`, which means the bytes themselves are corrupt: callers
+                // fall back to the Java helper only for this variant, not for genuine
+                // corruption.
+                return Err(UnsupportedPhdFeature {
+                    message: format!("Unsupported PHD record tag {unknown:#x}"),
+                });
+            }
+        }
+    }
+
+    recorder.finish()
+}
+
+fn read_phd_header<R: BufRead>(reader: &mut R) -> Result<AddressWidth, HprofSlurpError> {
+    let mut name_len_buf = [0u8; 2];
+    reader.read_exact(&mut name_len_buf)?;
+    let name_len = u16::from_be_bytes(name_len_buf) as usize;
+
+    let mut name_buf = vec![0u8; name_len];
+    reader.read_exact(&mut name_buf)?;
+    if name_buf != PHD_MAGIC {
+        return Err(InvalidHprofFile {
+            message: "Missing 'portable heap dump' magic".to_string(),
+        });
+    }
+
+    let mut flags_buf = [0u8; 4];
+    reader.read_exact(&mut flags_buf)?;
+    let flags = u32::from_be_bytes(flags_buf);
+
+    let version = ((flags >> 1) & 0xFF) as u8;
+    if version > PHD_MAX_SUPPORTED_VERSION {
+        // Same reasoning as the unknown-tag case above: a too-new version may have changed the
+        // record layout in ways this parser doesn't understand, so it's a fallback signal, not
+        // a corruption diagnostic.
+        return Err(UnsupportedPhdFeature {
+            message: format!("Unsupported PHD format version {version}"),
+        });
+    }
+
+    // Bit 0 of the header flags selects 64-bit addresses; the per-record encoding hints (e.g.
+    // relative vs. full-width references) are consumed per-record below.
+    Ok(if flags & 0x1 != 0 {
+        AddressWidth::Bits64
+    } else {
+        AddressWidth::Bits32
+    })
+}
+
+fn read_address<R: Read>(reader: &mut R, width: AddressWidth) -> Result<u64, HprofSlurpError> {
+    match width {
+        AddressWidth::Bits32 => {
+            let mut buf = [0u8; 4];
+            reader.read_exact(&mut buf)?;
+            Ok(u32::from_be_bytes(buf) as u64)
+        }
+        AddressWidth::Bits64 => {
+            let mut buf = [0u8; 8];
+            reader.read_exact(&mut buf)?;
+            Ok(u64::from_be_bytes(buf))
+        }
+    }
+}
+
+/// Discards exactly `len` bytes from `reader` without pre-allocating a buffer sized from an
+/// untrusted on-disk count — a truncated or adversarial PHD file could otherwise claim a
+/// many-GB reference list or array payload and cause a huge upfront allocation before
+/// `read_exact` ever gets a chance to fail.
+fn skip_exact<R: Read>(reader: &mut R, len: u64) -> Result<(), HprofSlurpError> {
+    let copied = io::copy(&mut reader.take(len), &mut io::sink())?;
+    if copied != len {
+        return Err(InvalidHprofFile {
+            message: "Unexpected end of stream while skipping a PHD record payload".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn read_class_record<R: Read>(
+    reader: &mut R,
+    width: AddressWidth,
+) -> Result<(u64, ClassInfo), HprofSlurpError> {
+    let address = read_address(reader, width)?;
+    // The native parser's `ResultRecorder` pipeline has no consumer for the superclass chain
+    // (that's a Java-helper-only feature, via DTFJ's `JavaClass.getSuperclass()`), so this is
+    // read only to advance the reader past it, not retained on `ClassInfo`.
+    let _superclass_address = read_address(reader, width)?;
+
+    let mut instance_size_buf = [0u8; 4];
+    reader.read_exact(&mut instance_size_buf)?;
+    let instance_size = u32::from_be_bytes(instance_size_buf);
+
+    let mut name_len_buf = [0u8; 2];
+    reader.read_exact(&mut name_len_buf)?;
+    let name_len = u16::from_be_bytes(name_len_buf) as usize;
+    let mut name_buf = vec![0u8; name_len];
+    reader.read_exact(&mut name_buf)?;
+    let name = String::from_utf8_lossy(&name_buf).into_owned();
+
+    Ok((
+        address,
+        ClassInfo {
+            name,
+            instance_size,
+        },
+    ))
+}
+
+fn read_object_record<R: Read>(
+    reader: &mut R,
+    width: AddressWidth,
+) -> Result<u64, HprofSlurpError> {
+    let _address = read_address(reader, width)?;
+    let class_address = read_address(reader, width)?;
+
+    // Reference lists are encoded as either full-width addresses or short relative offsets from
+    // the object's own address, selected by a flag byte immediately following the class address.
+    // The reference list itself isn't needed for the allocation summary (the object's shallow
+    // size comes from its class's declared `instance_size`), so it's just skipped over here.
+    let mut ref_encoding_buf = [0u8; 1];
+    reader.read_exact(&mut ref_encoding_buf)?;
+    let relative_refs = ref_encoding_buf[0] & 0x1 != 0;
+
+    let mut ref_count_buf = [0u8; 4];
+    reader.read_exact(&mut ref_count_buf)?;
+    let ref_count = u32::from_be_bytes(ref_count_buf) as u64;
+
+    let ref_entry_len = if relative_refs { 4 } else { width.byte_len() as u64 };
+    skip_exact(reader, ref_count * ref_entry_len)?;
+
+    Ok(class_address)
+}
+
+fn read_array_record<R: Read>(
+    reader: &mut R,
+    width: AddressWidth,
+) -> Result<(u64, u64), HprofSlurpError> {
+    let _address = read_address(reader, width)?;
+    let class_address = read_address(reader, width)?;
+
+    let mut element_count_buf = [0u8; 4];
+    reader.read_exact(&mut element_count_buf)?;
+    let element_count = u32::from_be_bytes(element_count_buf) as u64;
+
+    let mut element_size_buf = [0u8; 4];
+    reader.read_exact(&mut element_size_buf)?;
+    let element_size = u32::from_be_bytes(element_size_buf) as u64;
+
+    let payload_len = element_count * element_size;
+    skip_exact(reader, payload_len)?;
+
+    Ok((class_address, payload_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn header_bytes(version: u8, bits64: bool) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(PHD_MAGIC.len() as u16).to_be_bytes());
+        buf.extend_from_slice(PHD_MAGIC);
+        let mut flags: u32 = (version as u32) << 1;
+        if bits64 {
+            flags |= 0x1;
+        }
+        buf.extend_from_slice(&flags.to_be_bytes());
+        buf
+    }
+
+    #[test]
+    fn reads_address_width_from_header_flags() {
+        let mut reader = Cursor::new(header_bytes(0, false));
+        assert_eq!(read_phd_header(&mut reader).unwrap(), AddressWidth::Bits32);
+
+        let mut reader = Cursor::new(header_bytes(0, true));
+        assert_eq!(read_phd_header(&mut reader).unwrap(), AddressWidth::Bits64);
+    }
+
+    #[test]
+    fn rejects_an_unsupported_format_version() {
+        let mut reader = Cursor::new(header_bytes(PHD_MAX_SUPPORTED_VERSION + 1, false));
+        assert!(read_phd_header(&mut reader).is_err());
+    }
+
+    #[test]
+    fn rejects_a_missing_magic() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&3u16.to_be_bytes());
+        buf.extend_from_slice(b"php");
+        let mut reader = Cursor::new(buf);
+        assert!(read_phd_header(&mut reader).is_err());
+    }
+
+    #[test]
+    fn reads_addresses_at_both_widths() {
+        let mut reader = Cursor::new(0xAABBCCDDu32.to_be_bytes().to_vec());
+        assert_eq!(
+            read_address(&mut reader, AddressWidth::Bits32).unwrap(),
+            0xAABBCCDD
+        );
+
+        let mut reader = Cursor::new(0x0102030405060708u64.to_be_bytes().to_vec());
+        assert_eq!(
+            read_address(&mut reader, AddressWidth::Bits64).unwrap(),
+            0x0102030405060708
+        );
+    }
+
+    #[test]
+    fn reads_a_class_record() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x10u32.to_be_bytes()); // address
+        buf.extend_from_slice(&0x20u32.to_be_bytes()); // superclass address
+        buf.extend_from_slice(&48u32.to_be_bytes()); // instance size
+        let name = b"com.example.Foo";
+        buf.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        buf.extend_from_slice(name);
+
+        let mut reader = Cursor::new(buf);
+        let (address, info) = read_class_record(&mut reader, AddressWidth::Bits32).unwrap();
+        assert_eq!(address, 0x10);
+        assert_eq!(info.instance_size, 48);
+        assert_eq!(info.name, "com.example.Foo");
+    }
+
+    #[test]
+    fn reads_an_object_record_with_relative_references() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x10u32.to_be_bytes()); // object address
+        buf.extend_from_slice(&0x20u32.to_be_bytes()); // class address
+        buf.push(0x1); // relative_refs flag set
+        buf.extend_from_slice(&2u32.to_be_bytes()); // 2 references
+        buf.extend_from_slice(&[0u8; 2 * 4]); // two 4-byte relative offsets
+        buf.push(0xAB); // sentinel marking the start of the next record
+
+        let mut reader = Cursor::new(buf);
+        let class_address = read_object_record(&mut reader, AddressWidth::Bits64).unwrap();
+        assert_eq!(class_address, 0x20);
+        // Relative offsets are always 4 bytes regardless of address width, so exactly the 8
+        // bytes of reference list (not 16, as full 8-byte addresses would take) are skipped,
+        // leaving the reader positioned right at the sentinel.
+        let mut sentinel = [0u8; 1];
+        reader.read_exact(&mut sentinel).unwrap();
+        assert_eq!(sentinel[0], 0xAB);
+    }
+
+    #[test]
+    fn reads_an_object_record_with_absolute_references() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x10u64.to_be_bytes());
+        buf.extend_from_slice(&0x20u64.to_be_bytes());
+        buf.push(0x0); // relative_refs flag clear
+        buf.extend_from_slice(&2u32.to_be_bytes());
+        buf.extend_from_slice(&[0u8; 2 * 8]); // two full-width (64-bit) addresses
+        buf.push(0xAB); // sentinel marking the start of the next record
+
+        let mut reader = Cursor::new(buf);
+        let class_address = read_object_record(&mut reader, AddressWidth::Bits64).unwrap();
+        assert_eq!(class_address, 0x20);
+        // Absolute references are full address-width (8 bytes here), so 16 bytes of reference
+        // list are skipped, leaving the reader positioned right at the sentinel.
+        let mut sentinel = [0u8; 1];
+        reader.read_exact(&mut sentinel).unwrap();
+        assert_eq!(sentinel[0], 0xAB);
+    }
+
+    #[test]
+    fn record_instance_size_comes_from_the_class_not_the_reference_list() {
+        let mut classes = std::collections::HashMap::new();
+        classes.insert(
+            0x20,
+            ClassInfo {
+                name: "com.example.Foo".to_string(),
+                instance_size: 48,
+            },
+        );
+
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x10u32.to_be_bytes()); // object address
+        buf.extend_from_slice(&0x20u32.to_be_bytes()); // class address
+        buf.push(0x1); // relative_refs flag set
+        buf.extend_from_slice(&2u32.to_be_bytes()); // 2 references
+        buf.extend_from_slice(&[0u8; 2 * 4]); // two 4-byte relative offsets
+
+        let mut reader = Cursor::new(buf);
+        let class_address = read_object_record(&mut reader, AddressWidth::Bits32).unwrap();
+        let instance_size = classes[&class_address].instance_size as u64;
+        // The object's shallow size is the class's declared instance_size (48), not some
+        // function of how many references it holds (8 + 2 * 4 == 16, which would be wrong).
+        assert_eq!(instance_size, 48);
+    }
+
+    #[test]
+    fn reads_an_array_record() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0x10u32.to_be_bytes()); // array address
+        buf.extend_from_slice(&0x20u32.to_be_bytes()); // class address
+        buf.extend_from_slice(&10u32.to_be_bytes()); // element count
+        buf.extend_from_slice(&4u32.to_be_bytes()); // element size
+        buf.extend_from_slice(&[0u8; 40]); // payload
+
+        let mut reader = Cursor::new(buf);
+        let (class_address, size) = read_array_record(&mut reader, AddressWidth::Bits32).unwrap();
+        assert_eq!(class_address, 0x20);
+        assert_eq!(size, 40);
+    }
+}
@@ -0,0 +1,28 @@
+//! The handful of hprof/PHD facts [`crate::result_recorder::ResultRecorder`] actually needs to
+//! compute the per-class allocation summary. Both [`crate::parser::record_stream_parser`] (the
+//! binary hprof stream) and [`crate::parser::phd_parser`] (the PHD tagged-record stream) lower
+//! their own, very different wire formats down to this shared shape before handing results to
+//! the recorder, so the recorder itself never has to know which format produced them.
+
+/// One fact extracted from a heap dump, in whichever format it arrived in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    /// A class was declared: `class_object_id` is the id objects reference to claim membership,
+    /// and `instance_size` is the shallow size (in bytes) of a plain (non-array) instance.
+    ClassDump {
+        class_object_id: u64,
+        class_name: String,
+        instance_size: u64,
+    },
+    /// A plain object instance. Its shallow size comes from the `instance_size` declared by its
+    /// class, not from anything carried on the allocation record itself.
+    InstanceAllocation { class_object_id: u64 },
+    /// An array instance. Unlike a plain object, an array's shallow size varies per instance
+    /// (it depends on the element count), so it's carried on the record directly.
+    ArrayAllocation { class_object_id: u64, size_bytes: u64 },
+    /// A string from the hprof string table (`STRING_IN_UTF8`; the PHD parser emits none). Most of these
+    /// back a class or field name and are never looked at again; they're only forwarded as
+    /// `Record`s (rather than kept solely in the parser's own lookup table) so `--list-strings`
+    /// has something to show on the native-parser path.
+    StringLiteral { text: String },
+}
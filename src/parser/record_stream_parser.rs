@@ -0,0 +1,391 @@
+//! Parses the binary hprof record stream (the bytes that follow [`crate::parser::file_header_parser`]'s
+//! header) into [`crate::parser::record::Record`] values.
+//!
+//! Top-level hprof records are a flat `(tag: u8, timestamp: u32, length: u32, body: [u8; length])`
+//! sequence; every tag's total size is knowable up front from its own `length` field, so a tag
+//! this parser doesn't care about (stack traces, load-class bookkeeping aside, thread starts, the
+//! heap-dump-end marker, ...) is simply skipped. `HEAP_DUMP`/`HEAP_DUMP_SEGMENT` bodies are
+//! themselves a sequence of sub-records describing GC roots, classes, and object/array
+//! instances — only the four sub-record kinds needed for the allocation summary
+//! (`CLASS_DUMP`, `INSTANCE_DUMP`, `OBJ_ARRAY_DUMP`, `PRIM_ARRAY_DUMP`) are turned into `Record`s;
+//! GC root sub-records are skipped using their (fixed, id-size-dependent) lengths.
+//!
+//! Bytes can arrive split across arbitrary chunk boundaries (the pre-fetcher hands over whatever
+//! it managed to read), so incomplete records are buffered and retried on the next call to
+//! [`HprofRecordStreamParser::parse_chunk`] rather than treated as a parse error.
+
+use std::thread;
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::errors::HprofSlurpError;
+use crate::errors::HprofSlurpError::InvalidHprofFile;
+use crate::parser::record::Record;
+
+const TAG_STRING_IN_UTF8: u8 = 0x01;
+const TAG_LOAD_CLASS: u8 = 0x02;
+const TAG_HEAP_DUMP: u8 = 0x0C;
+const TAG_HEAP_DUMP_SEGMENT: u8 = 0x1C;
+
+const SUB_TAG_GC_ROOT_UNKNOWN: u8 = 0xFF;
+const SUB_TAG_GC_ROOT_JNI_GLOBAL: u8 = 0x01;
+const SUB_TAG_GC_ROOT_JNI_LOCAL: u8 = 0x02;
+const SUB_TAG_GC_ROOT_JAVA_FRAME: u8 = 0x03;
+const SUB_TAG_GC_ROOT_NATIVE_STACK: u8 = 0x04;
+const SUB_TAG_GC_ROOT_STICKY_CLASS: u8 = 0x05;
+const SUB_TAG_GC_ROOT_THREAD_BLOCK: u8 = 0x06;
+const SUB_TAG_GC_ROOT_MONITOR_USED: u8 = 0x07;
+const SUB_TAG_GC_ROOT_THREAD_OBJ: u8 = 0x08;
+const SUB_TAG_GC_CLASS_DUMP: u8 = 0x20;
+const SUB_TAG_GC_INSTANCE_DUMP: u8 = 0x21;
+const SUB_TAG_GC_OBJ_ARRAY_DUMP: u8 = 0x22;
+const SUB_TAG_GC_PRIM_ARRAY_DUMP: u8 = 0x23;
+
+/// A cursor over a fully-buffered byte slice. Unlike the top-level framing (which has to cope
+/// with chunk boundaries), everything read through this cursor is known to be present in full —
+/// a top-level record is only handed to [`parse_heap_dump_body`] once its whole declared length has
+/// arrived — so short reads here indicate a genuinely malformed dump, not a boundary to retry.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Cursor { bytes, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], HprofSlurpError> {
+        if self.remaining() < len {
+            return Err(InvalidHprofFile {
+                message: "Truncated hprof sub-record".to_string(),
+            });
+        }
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, HprofSlurpError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, HprofSlurpError> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, HprofSlurpError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn id(&mut self, id_size: u8) -> Result<u64, HprofSlurpError> {
+        let bytes = self.take(id_size as usize)?;
+        let mut buf = [0u8; 8];
+        buf[8 - bytes.len()..].copy_from_slice(bytes);
+        Ok(u64::from_be_bytes(buf))
+    }
+}
+
+/// The synthetic `class_object_id` `PRIM_ARRAY_DUMP` attributes allocations to, since primitive
+/// arrays have no `CLASS_DUMP` record of their own. Kept well above any real id a dump could
+/// assign (ids are allocated from the low end) so it can't collide with an actual class.
+pub(crate) fn primitive_array_synthetic_class_id(element_type: u8) -> u64 {
+    u64::MAX - element_type as u64
+}
+
+/// Display name for the synthetic class id [`primitive_array_synthetic_class_id`] mints, so a
+/// `byte[]` allocation resolves to something readable instead of `<unresolved class @...>`.
+pub(crate) fn primitive_array_class_name(element_type: u8) -> &'static str {
+    match element_type {
+        4 => "boolean[]",
+        5 => "char[]",
+        6 => "float[]",
+        7 => "double[]",
+        8 => "byte[]",
+        9 => "short[]",
+        10 => "int[]",
+        11 => "long[]",
+        _ => "<unknown primitive array type>",
+    }
+}
+
+/// Size in bytes of a JVM basic type tag, as used in `GC_CLASS_DUMP`'s constant pool/static
+/// field entries and `GC_PRIM_ARRAY_DUMP`'s element type — `2` (object) and `4` (boolean)
+/// through `11` (long) are the fixed set defined by the hprof format.
+fn basic_type_size(type_tag: u8, id_size: u8) -> Result<usize, HprofSlurpError> {
+    match type_tag {
+        2 => Ok(id_size as usize), // object
+        4 => Ok(1),                // boolean
+        5 => Ok(2),                // char
+        6 => Ok(4),                // float
+        7 => Ok(8),                // double
+        8 => Ok(1),                // byte
+        9 => Ok(2),                // short
+        10 => Ok(4),               // int
+        11 => Ok(8),               // long
+        other => Err(InvalidHprofFile {
+            message: format!("Unknown JVM basic type tag {other:#x}"),
+        }),
+    }
+}
+
+fn parse_class_dump(cursor: &mut Cursor, id_size: u8) -> Result<(u64, u64), HprofSlurpError> {
+    let class_object_id = cursor.id(id_size)?;
+    let _stack_trace_serial = cursor.u32()?;
+    let _super_class_object_id = cursor.id(id_size)?;
+    let _class_loader_id = cursor.id(id_size)?;
+    let _signers_id = cursor.id(id_size)?;
+    let _protection_domain_id = cursor.id(id_size)?;
+    let _reserved1 = cursor.id(id_size)?;
+    let _reserved2 = cursor.id(id_size)?;
+    let instance_size = cursor.u32()? as u64;
+
+    let constant_pool_size = cursor.u16()?;
+    for _ in 0..constant_pool_size {
+        let _cp_index = cursor.u16()?;
+        let type_tag = cursor.u8()?;
+        cursor.take(basic_type_size(type_tag, id_size)?)?;
+    }
+
+    let static_field_count = cursor.u16()?;
+    for _ in 0..static_field_count {
+        let _name_id = cursor.id(id_size)?;
+        let type_tag = cursor.u8()?;
+        cursor.take(basic_type_size(type_tag, id_size)?)?;
+    }
+
+    let instance_field_count = cursor.u16()?;
+    for _ in 0..instance_field_count {
+        let _name_id = cursor.id(id_size)?;
+        let _type_tag = cursor.u8()?;
+    }
+
+    Ok((class_object_id, instance_size))
+}
+
+/// Parses one `HEAP_DUMP`/`HEAP_DUMP_SEGMENT` body into the `Record`s it describes, resolving
+/// class names from `classes_loaded` (filled in by earlier `LOAD_CLASS`/`STRING_IN_UTF8`
+/// records) as it goes.
+fn parse_heap_dump_body(
+    body: &[u8],
+    id_size: u8,
+    classes_loaded: &std::collections::HashMap<u64, u64>,
+    strings: &std::collections::HashMap<u64, String>,
+    out: &mut Vec<Record>,
+) -> Result<(), HprofSlurpError> {
+    let mut cursor = Cursor::new(body);
+    while cursor.remaining() > 0 {
+        let sub_tag = cursor.u8()?;
+        match sub_tag {
+            SUB_TAG_GC_ROOT_UNKNOWN | SUB_TAG_GC_ROOT_STICKY_CLASS | SUB_TAG_GC_ROOT_MONITOR_USED => {
+                cursor.take(id_size as usize)?;
+            }
+            SUB_TAG_GC_ROOT_JNI_GLOBAL => {
+                cursor.take(2 * id_size as usize)?;
+            }
+            SUB_TAG_GC_ROOT_JNI_LOCAL
+            | SUB_TAG_GC_ROOT_JAVA_FRAME
+            | SUB_TAG_GC_ROOT_THREAD_OBJ => {
+                cursor.take(id_size as usize + 8)?;
+            }
+            SUB_TAG_GC_ROOT_NATIVE_STACK | SUB_TAG_GC_ROOT_THREAD_BLOCK => {
+                cursor.take(id_size as usize + 4)?;
+            }
+            SUB_TAG_GC_CLASS_DUMP => {
+                let (class_object_id, instance_size) = parse_class_dump(&mut cursor, id_size)?;
+                let class_name = classes_loaded
+                    .get(&class_object_id)
+                    .and_then(|name_id| strings.get(name_id))
+                    .cloned()
+                    .unwrap_or_else(|| format!("<unresolved class @{class_object_id:#x}>"));
+                out.push(Record::ClassDump {
+                    class_object_id,
+                    class_name,
+                    instance_size,
+                });
+            }
+            SUB_TAG_GC_INSTANCE_DUMP => {
+                let _object_id = cursor.id(id_size)?;
+                let _stack_trace_serial = cursor.u32()?;
+                let class_object_id = cursor.id(id_size)?;
+                let num_bytes = cursor.u32()? as usize;
+                cursor.take(num_bytes)?;
+                out.push(Record::InstanceAllocation { class_object_id });
+            }
+            SUB_TAG_GC_OBJ_ARRAY_DUMP => {
+                let _array_object_id = cursor.id(id_size)?;
+                let _stack_trace_serial = cursor.u32()?;
+                let num_elements = cursor.u32()? as u64;
+                let array_class_object_id = cursor.id(id_size)?;
+                cursor.take(num_elements as usize * id_size as usize)?;
+                out.push(Record::ArrayAllocation {
+                    class_object_id: array_class_object_id,
+                    size_bytes: num_elements * id_size as u64,
+                });
+            }
+            SUB_TAG_GC_PRIM_ARRAY_DUMP => {
+                let _array_object_id = cursor.id(id_size)?;
+                let _stack_trace_serial = cursor.u32()?;
+                let num_elements = cursor.u32()? as u64;
+                let element_type = cursor.u8()?;
+                let element_size = basic_type_size(element_type, id_size)? as u64;
+                cursor.take((num_elements * element_size) as usize)?;
+                // Primitive arrays have no class-dump record of their own; the allocation is
+                // attributed to a synthetic per-element-type class id so it still shows up in
+                // the summary instead of being silently dropped.
+                out.push(Record::ArrayAllocation {
+                    class_object_id: primitive_array_synthetic_class_id(element_type),
+                    size_bytes: num_elements * element_size,
+                });
+            }
+            unknown => {
+                return Err(InvalidHprofFile {
+                    message: format!("Unsupported heap dump sub-record tag {unknown:#x}"),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Streams [`Record`]s out of the raw hprof byte sequence that follows the file header, one
+/// chunk of input at a time.
+pub struct HprofRecordStreamParser {
+    debug_mode: bool,
+    bytes_consumed: usize,
+    id_size: u8,
+    buffer: Vec<u8>,
+    classes_loaded: std::collections::HashMap<u64, u64>,
+    strings: std::collections::HashMap<u64, String>,
+}
+
+impl HprofRecordStreamParser {
+    pub fn new(debug_mode: bool, id_size: u8, header_len: usize, initial_buffer: Vec<u8>) -> Self {
+        HprofRecordStreamParser {
+            debug_mode,
+            bytes_consumed: header_len,
+            id_size,
+            buffer: initial_buffer,
+            classes_loaded: std::collections::HashMap::new(),
+            strings: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Feeds `bytes` in, returning every `Record` that could be fully parsed out of the buffer
+    /// so far. Bytes belonging to a record whose length hasn't fully arrived yet are retained
+    /// for the next call.
+    pub fn parse_chunk(&mut self, bytes: &[u8]) -> Result<Vec<Record>, HprofSlurpError> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut records = Vec::new();
+        let mut offset = 0;
+        loop {
+            let remaining = &self.buffer[offset..];
+            if remaining.len() < 9 {
+                break;
+            }
+            let tag = remaining[0];
+            let length = u32::from_be_bytes(remaining[5..9].try_into().unwrap()) as usize;
+            if remaining.len() < 9 + length {
+                break;
+            }
+            let body = &remaining[9..9 + length];
+
+            if self.debug_mode {
+                eprintln!("hprof record tag={tag:#x} length={length}");
+            }
+
+            match tag {
+                TAG_STRING_IN_UTF8 => {
+                    if body.len() < self.id_size as usize {
+                        return Err(InvalidHprofFile {
+                            message: "Truncated STRING_IN_UTF8 record".to_string(),
+                        });
+                    }
+                    let mut id_cursor = Cursor::new(body);
+                    let id = id_cursor.id(self.id_size)?;
+                    let text = String::from_utf8_lossy(&body[self.id_size as usize..]).into_owned();
+                    self.strings.insert(id, text.clone());
+                    records.push(Record::StringLiteral { text });
+                }
+                TAG_LOAD_CLASS => {
+                    let mut cursor = Cursor::new(body);
+                    let _class_serial = cursor.u32()?;
+                    let class_object_id = cursor.id(self.id_size)?;
+                    let _stack_trace_serial = cursor.u32()?;
+                    let class_name_id = cursor.id(self.id_size)?;
+                    self.classes_loaded.insert(class_object_id, class_name_id);
+                }
+                TAG_HEAP_DUMP | TAG_HEAP_DUMP_SEGMENT => {
+                    parse_heap_dump_body(
+                        body,
+                        self.id_size,
+                        &self.classes_loaded,
+                        &self.strings,
+                        &mut records,
+                    )?;
+                }
+                _ => {
+                    // Every other top-level tag (stack frames/traces, thread starts, the
+                    // heap-dump-end marker, ...) carries an explicit length and isn't needed for
+                    // the allocation summary, so it's skipped outright.
+                }
+            }
+
+            offset += 9 + length;
+            self.bytes_consumed += 9 + length;
+        }
+
+        self.buffer.drain(0..offset);
+        Ok(records)
+    }
+
+    fn progress(&self) -> usize {
+        self.bytes_consumed
+    }
+
+    /// Spawns a thread that drains raw byte chunks from `receive_data`, turns them into
+    /// `Record`s, and forwards those downstream to the recorder — mirroring how
+    /// [`crate::prefetch_reader::PrefetchReader::start`] hands off to this parser in the first
+    /// place. Pooled buffers are handed back on both ends (`send_pooled_data` for the raw-byte
+    /// pool, `receive_pooled_vec`/`send_records` for the `Vec<Record>` pool) so neither side
+    /// reallocates once steady state is reached.
+    pub fn start(
+        mut self,
+        receive_data: Receiver<Vec<u8>>,
+        send_pooled_data: Sender<Vec<u8>>,
+        send_progress: Sender<usize>,
+        receive_pooled_vec: Receiver<Vec<Record>>,
+        send_records: Sender<Vec<Record>>,
+    ) -> Result<JoinHandle<()>, HprofSlurpError> {
+        let handle = thread::Builder::new()
+            .name("hprof-record-stream-parser".to_string())
+            .spawn(move || {
+                while let Ok(chunk) = receive_data.recv() {
+                    let parsed = self
+                        .parse_chunk(&chunk)
+                        .expect("hprof record stream parse error");
+                    let _ = send_pooled_data.send(chunk);
+
+                    if !parsed.is_empty() {
+                        let mut out_buf = receive_pooled_vec.recv().unwrap_or_default();
+                        out_buf.extend(parsed);
+                        if send_records.send(out_buf).is_err() {
+                            break;
+                        }
+                    }
+
+                    if send_progress.send(self.progress()).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("failed to spawn hprof-record-stream-parser thread");
+        Ok(handle)
+    }
+}
@@ -0,0 +1,84 @@
+//! Reads raw bytes off the decompressed dump stream on a dedicated thread, so the record-stream
+//! parser downstream never blocks on IO (disk, decompression, or a network socket) while
+//! decoding. Pooled buffers are reused from both directions via `send_data`/`receive_pooled_data`
+//! so steady-state operation doesn't reallocate per chunk.
+
+use std::io::{BufRead, Read};
+use std::thread;
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::errors::HprofSlurpError;
+
+/// Pulls bytes from any `BufRead + Send` source — typically [`crate::slurp::DumpReader`], but
+/// generic so a plain file, stdin, or a network stream can all be prefetched through the same
+/// pipeline — and forwards them to the record-stream parser in `buffer_size`-sized chunks.
+pub struct PrefetchReader<S> {
+    reader: S,
+    total_len: Option<usize>,
+    header_len: usize,
+    buffer_size: usize,
+}
+
+impl<S: BufRead + Send + 'static> PrefetchReader<S> {
+    pub fn new(reader: S, total_len: Option<usize>, header_len: usize, buffer_size: usize) -> Self {
+        PrefetchReader {
+            reader,
+            total_len,
+            header_len,
+            buffer_size,
+        }
+    }
+
+    /// Spawns a thread that reads chunks until EOF, sending each one to `send_data` and pulling
+    /// the next buffer to fill from `receive_pooled_data` (falling back to a fresh allocation
+    /// once the pool runs dry, e.g. at startup). When `total_len` is known, the stream is
+    /// expected to yield exactly `total_len - header_len` bytes (the header itself having
+    /// already been consumed by the caller before handing `reader` over) — coming up short means
+    /// the dump was truncated.
+    pub fn start(
+        mut self,
+        send_data: Sender<Vec<u8>>,
+        receive_pooled_data: Receiver<Vec<u8>>,
+    ) -> Result<JoinHandle<()>, HprofSlurpError> {
+        let expected_remaining = self
+            .total_len
+            .map(|len| len.saturating_sub(self.header_len) as u64);
+
+        let handle = thread::Builder::new()
+            .name("hprof-prefetch-reader".to_string())
+            .spawn(move || {
+                let mut bytes_read: u64 = 0;
+                loop {
+                    let mut buffer = receive_pooled_data
+                        .try_recv()
+                        .unwrap_or_else(|_| Vec::with_capacity(self.buffer_size));
+                    buffer.resize(self.buffer_size, 0);
+
+                    let read = self
+                        .reader
+                        .read(&mut buffer)
+                        .expect("hprof prefetch read error");
+                    if read == 0 {
+                        break;
+                    }
+                    buffer.truncate(read);
+                    bytes_read += read as u64;
+
+                    if send_data.send(buffer).is_err() {
+                        break;
+                    }
+                }
+
+                if let Some(expected) = expected_remaining {
+                    assert_eq!(
+                        bytes_read, expected,
+                        "hprof stream ended after {bytes_read} bytes, expected {expected}"
+                    );
+                }
+            })
+            .expect("failed to spawn hprof-prefetch-reader thread");
+        Ok(handle)
+    }
+}
@@ -0,0 +1,215 @@
+//! Aggregates the [`Record`]s emitted by either format-specific parser —
+//! [`crate::parser::record_stream_parser`] for binary hprof, [`crate::parser::phd_parser`] for
+//! PHD — into the final per-class allocation summary. Neither parser needs to know anything
+//! about the other's wire format; they just hand over `Record`s as they're decoded.
+//!
+//! This type, [`crate::prefetch_reader::PrefetchReader`] and `HprofRecordStreamParser` are the
+//! shared infrastructure the native-parser pipeline has depended on since it was first wired up;
+//! treat them as a single unit when reading the history rather than attributing them to whichever
+//! commit happened to touch this file last.
+
+use std::collections::HashMap;
+use std::thread;
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{Receiver, Sender};
+use indoc::formatdoc;
+
+use crate::errors::HprofSlurpError;
+use crate::parser::record::Record;
+use crate::parser::record_stream_parser::{primitive_array_class_name, primitive_array_synthetic_class_id};
+use crate::rendered_result::{ClassAllocationStats, RenderedResult};
+
+/// Primitive-array element type tags `PRIM_ARRAY_DUMP` can carry, per the hprof format.
+const PRIMITIVE_ARRAY_ELEMENT_TYPES: [u8; 8] = [4, 5, 6, 7, 8, 9, 10, 11];
+
+struct ClassMeta {
+    name: String,
+    instance_size: u64,
+}
+
+#[derive(Default)]
+struct ClassStats {
+    instance_count: u64,
+    largest_allocation_bytes: u64,
+    allocation_size_bytes: u64,
+}
+
+/// Folds a stream of [`Record`]s into a running per-class tally, then renders the final
+/// [`RenderedResult`] once the stream is exhausted.
+pub struct ResultRecorder {
+    id_size: u8,
+    list_strings: bool,
+    classes: HashMap<u64, ClassMeta>,
+    stats_by_class_name: HashMap<String, ClassStats>,
+    total_objects: u64,
+    captured_strings: Vec<String>,
+}
+
+impl ResultRecorder {
+    pub fn new(id_size: u8, list_strings: bool) -> Self {
+        // Primitive arrays have no `CLASS_DUMP` record of their own (see
+        // `primitive_array_synthetic_class_id`), so the classes a `PRIM_ARRAY_DUMP` resolves
+        // against are seeded up front rather than waiting to see one declared.
+        let classes = PRIMITIVE_ARRAY_ELEMENT_TYPES
+            .into_iter()
+            .map(|element_type| {
+                (
+                    primitive_array_synthetic_class_id(element_type),
+                    ClassMeta {
+                        name: primitive_array_class_name(element_type).to_string(),
+                        instance_size: 0,
+                    },
+                )
+            })
+            .collect();
+
+        ResultRecorder {
+            id_size,
+            list_strings,
+            classes,
+            stats_by_class_name: HashMap::new(),
+            total_objects: 0,
+            captured_strings: Vec::new(),
+        }
+    }
+
+    /// Folds one batch of records into the running tallies, returning the (now empty) `Vec` so
+    /// the caller can hand it back to the pooled-buffer channel instead of reallocating.
+    pub fn record(&mut self, mut records: Vec<Record>) -> Result<Vec<Record>, HprofSlurpError> {
+        for record in records.drain(..) {
+            match record {
+                Record::ClassDump {
+                    class_object_id,
+                    class_name,
+                    instance_size,
+                } => {
+                    self.classes.insert(
+                        class_object_id,
+                        ClassMeta {
+                            name: class_name,
+                            instance_size,
+                        },
+                    );
+                }
+                Record::InstanceAllocation { class_object_id } => {
+                    let (class_name, size_bytes) = self.resolve(class_object_id);
+                    self.tally(class_name, size_bytes);
+                }
+                Record::ArrayAllocation {
+                    class_object_id,
+                    size_bytes,
+                } => {
+                    let (class_name, _) = self.resolve(class_object_id);
+                    self.tally(class_name, size_bytes);
+                }
+                Record::StringLiteral { text } => {
+                    if self.list_strings {
+                        self.captured_strings.push(text);
+                    }
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    /// Looks up the declared name/shallow-size for a class that was (or wasn't yet) seen via a
+    /// `ClassDump` record. A dump that references a class before declaring it — or a PHD/hprof
+    /// variant this parser doesn't fully understand — still gets counted, just under a
+    /// placeholder name, rather than dropped from the summary entirely.
+    fn resolve(&self, class_object_id: u64) -> (String, u64) {
+        match self.classes.get(&class_object_id) {
+            Some(meta) => (meta.name.clone(), meta.instance_size),
+            None => (format!("<unresolved class @{class_object_id:#x}>"), 0),
+        }
+    }
+
+    fn tally(&mut self, class_name: String, size_bytes: u64) {
+        self.total_objects += 1;
+        let stats = self.stats_by_class_name.entry(class_name).or_default();
+        stats.instance_count += 1;
+        stats.largest_allocation_bytes = stats.largest_allocation_bytes.max(size_bytes);
+        stats.allocation_size_bytes += size_bytes;
+    }
+
+    /// Consumes the recorder and renders the final summary, in the same shape the Java-helper
+    /// path's `render_helper_response` produces.
+    pub fn finish(self) -> Result<RenderedResult, HprofSlurpError> {
+        let memory_usage = self
+            .stats_by_class_name
+            .into_iter()
+            .map(|(class_name, stats)| {
+                ClassAllocationStats::new(
+                    class_name,
+                    stats.instance_count,
+                    stats.largest_allocation_bytes,
+                    stats.allocation_size_bytes,
+                )
+            })
+            .collect();
+
+        let summary = formatdoc!(
+            "\nFile content summary:\n  Address size: {id_size} bytes\n  Distinct classes: {classes}\n  Objects counted: {objects}",
+            id_size = self.id_size,
+            // `stats_by_class_name` (not `self.classes.len()`) since `classes` also carries the
+            // synthetic primitive-array entries seeded up front in `new`, whether or not this
+            // dump ever actually allocated one.
+            classes = self.stats_by_class_name.len(),
+            objects = self.total_objects,
+        );
+
+        let thread_info = "\nThread information:\n  Detailed stack traces are not captured by the native parser.\n".to_string();
+
+        let captured_strings = if self.list_strings {
+            Some(if self.captured_strings.is_empty() {
+                "\nCaptured strings:\n  No strings were captured for this dump.\n".to_string()
+            } else {
+                let listing = self
+                    .captured_strings
+                    .iter()
+                    .map(|s| format!("  {s}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("\nCaptured strings:\n{listing}\n")
+            })
+        } else {
+            None
+        };
+
+        Ok(RenderedResult {
+            summary,
+            thread_info,
+            memory_usage,
+            duplicated_strings: None,
+            captured_strings,
+        })
+    }
+
+    /// Spawns a thread that drains parsed-record batches from `receive_records`, folds them into
+    /// the running tallies, and hands each (now empty) `Vec` back via `send_pooled_vec` so the
+    /// parser feeding it never reallocates in steady state. Renders and sends the final result
+    /// once `receive_records` is closed.
+    pub fn start(
+        mut self,
+        receive_records: Receiver<Vec<Record>>,
+        send_result: Sender<RenderedResult>,
+        send_pooled_vec: Sender<Vec<Record>>,
+    ) -> Result<JoinHandle<()>, HprofSlurpError> {
+        let handle = thread::Builder::new()
+            .name("hprof-result-recorder".to_string())
+            .spawn(move || {
+                while let Ok(records) = receive_records.recv() {
+                    let reused = self
+                        .record(records)
+                        .expect("record aggregation should never fail");
+                    if send_pooled_vec.send(reused).is_err() {
+                        break;
+                    }
+                }
+                let result = self.finish().expect("result rendering should never fail");
+                let _ = send_result.send(result);
+            })
+            .expect("failed to spawn hprof-result-recorder thread");
+        Ok(handle)
+    }
+}
@@ -1,16 +1,24 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
+use flate2::bufread::GzDecoder;
 use indicatif::{ProgressBar, ProgressStyle};
+use ruzstd::StreamingDecoder;
 
 use crossbeam_channel::{Receiver, Sender};
 
 use crate::errors::HprofSlurpError;
 use crate::errors::HprofSlurpError::{
-    InvalidHeaderSize, InvalidHprofFile, InvalidIdSize, StdThreadError, UnsupportedDumpFormat,
-    UnsupportedIdSize,
+    CompressionError, InvalidHeaderSize, InvalidHprofFile, InvalidIdSize, StdThreadError,
+    UnsupportedDumpFormat, UnsupportedIdSize, UnsupportedPhdFeature,
+};
+use crate::java_bridge::{
+    analyze_hotspot_core_with_java_helper, analyze_hotspot_pid_with_java_helper,
+    analyze_with_java_helper,
 };
-use crate::java_bridge::analyze_with_java_helper;
 use crate::parser::file_header_parser::{FileHeader, parse_file_header};
 use crate::parser::record::Record;
 use crate::parser::record_stream_parser::HprofRecordStreamParser;
@@ -24,6 +32,9 @@ const FILE_HEADER_LENGTH: usize = 31;
 // 64 MB buffer performs nicely (higher is faster but increases the memory consumption)
 pub const READ_BUFFER_SIZE: usize = 64 * 1024 * 1024;
 
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum DumpFormat {
     Hprof,
@@ -31,6 +42,29 @@ enum DumpFormat {
     OpenJ9Core,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompressionFormat {
+    None,
+    Gzip,
+    Zstd,
+}
+
+fn detect_compression(buf: &[u8]) -> CompressionFormat {
+    if buf.starts_with(&GZIP_MAGIC) {
+        CompressionFormat::Gzip
+    } else if buf.starts_with(&ZSTD_MAGIC) {
+        CompressionFormat::Zstd
+    } else {
+        CompressionFormat::None
+    }
+}
+
+/// HotSpot core files are also plain ELF and can't be told apart from an OpenJ9 core by magic
+/// bytes alone; telling them apart needs either a DTFJ probe or the Serviceability Agent, neither
+/// of which this function has access to. A caller who already knows they're pointing at a HotSpot
+/// core (and, for SA, the exact `java` executable that produced it) should call
+/// [`slurp_hotspot_core`]/[`slurp_hotspot_pid`] directly instead of going through `slurp_reader`'s
+/// format sniffing.
 fn detect_dump_format(buf: &[u8]) -> Result<DumpFormat, HprofSlurpError> {
     if buf.len() >= 4 && buf.starts_with(&[0x7F, b'E', b'L', b'F']) {
         return Ok(DumpFormat::OpenJ9Core);
@@ -52,6 +86,169 @@ fn detect_dump_format(buf: &[u8]) -> Result<DumpFormat, HprofSlurpError> {
     })
 }
 
+/// Counts the bytes read from the underlying compressed file, independent of how many
+/// decompressed bytes the parser ends up consuming. Used to drive the progress bar when the
+/// uncompressed stream length cannot be known up front.
+struct CountingReader<R> {
+    inner: R,
+    read_bytes: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read_bytes.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for CountingReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.read_bytes.fetch_add(amt as u64, Ordering::Relaxed);
+        self.inner.consume(amt)
+    }
+}
+
+/// The hprof byte stream, after any gzip/zstd framing has been stripped away. Generic over the
+/// underlying source so a file, a socket, stdin, or an in-memory buffer can all be slurped
+/// through the same pipeline.
+enum DumpReader<R> {
+    Raw(R),
+    Gzip(BufReader<GzDecoder<CountingReader<R>>>),
+    Zstd(BufReader<StreamingDecoder<CountingReader<R>>>),
+}
+
+impl<R: BufRead> Read for DumpReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            DumpReader::Raw(r) => r.read(buf),
+            DumpReader::Gzip(r) => r.read(buf),
+            DumpReader::Zstd(r) => r.read(buf),
+        }
+    }
+}
+
+impl<R: BufRead> BufRead for DumpReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        match self {
+            DumpReader::Raw(r) => r.fill_buf(),
+            DumpReader::Gzip(r) => r.fill_buf(),
+            DumpReader::Zstd(r) => r.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            DumpReader::Raw(r) => r.consume(amt),
+            DumpReader::Gzip(r) => r.consume(amt),
+            DumpReader::Zstd(r) => r.consume(amt),
+        }
+    }
+}
+
+/// Wraps `source` in a decompressor when its head matches a known compression magic.
+/// `total_len`, when known (e.g. from `File::metadata`), is passed through unchanged for an
+/// uncompressed source as the returned stream's known (decompressed) length; the decompressed
+/// size of a compressed source can't be known upfront, so it's dropped there instead — but
+/// `total_len` is still the compressed source's own known length, which is handed back
+/// separately as `known_compressed_len` so a caller can still bound a progress bar off
+/// `compressed_bytes_read` even though the decompressed length is unknown.
+fn open_dump_reader<R: BufRead>(
+    mut source: R,
+    total_len: Option<usize>,
+    compressed_bytes_read: Arc<AtomicU64>,
+) -> Result<(DumpReader<R>, Option<usize>, Option<usize>), HprofSlurpError> {
+    let probe_buffer = source.fill_buf()?;
+    if probe_buffer.is_empty() {
+        return Err(UnsupportedDumpFormat {
+            message: "Empty input stream".to_string(),
+        });
+    }
+
+    match detect_compression(probe_buffer) {
+        CompressionFormat::None => Ok((DumpReader::Raw(source), total_len, None)),
+        CompressionFormat::Gzip => {
+            let counting = CountingReader {
+                inner: source,
+                read_bytes: compressed_bytes_read,
+            };
+            let decoder = BufReader::new(GzDecoder::new(counting));
+            Ok((DumpReader::Gzip(decoder), None, total_len))
+        }
+        CompressionFormat::Zstd => {
+            let counting = CountingReader {
+                inner: source,
+                read_bytes: compressed_bytes_read,
+            };
+            let decoder = StreamingDecoder::new(counting).map_err(|e| CompressionError {
+                message: format!("Failed to initialize zstd decoder: {e}"),
+            })?;
+            Ok((DumpReader::Zstd(BufReader::new(decoder)), None, total_len))
+        }
+    }
+}
+
+/// The on-disk path the Java helper should read. It shells out to a JVM that only knows how to
+/// `open()` raw bytes — unlike the native path's `DumpReader`, it can't strip gzip/zstd framing
+/// itself — so a compressed `dump_path` is decompressed into a temp file first; an uncompressed
+/// one is passed through unchanged. The temp file (if any) is removed when this is dropped.
+struct JavaHelperInput {
+    path: PathBuf,
+    is_temp_file: bool,
+}
+
+impl JavaHelperInput {
+    fn path_str(&self) -> Result<&str, HprofSlurpError> {
+        self.path.to_str().ok_or_else(|| UnsupportedDumpFormat {
+            message: "Dump path is not valid UTF-8".to_string(),
+        })
+    }
+}
+
+impl Drop for JavaHelperInput {
+    fn drop(&mut self) {
+        if self.is_temp_file {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn prepare_java_helper_input(dump_path: &str) -> Result<JavaHelperInput, HprofSlurpError> {
+    let mut probe = BufReader::new(File::open(dump_path)?);
+    let compression = detect_compression(probe.fill_buf()?);
+    if compression == CompressionFormat::None {
+        return Ok(JavaHelperInput {
+            path: PathBuf::from(dump_path),
+            is_temp_file: false,
+        });
+    }
+
+    let temp_path =
+        std::env::temp_dir().join(format!("hprof-slurp-helper-input-{}.tmp", std::process::id()));
+    let mut temp_file = File::create(&temp_path)?;
+    match compression {
+        CompressionFormat::Gzip => {
+            io::copy(&mut GzDecoder::new(probe), &mut temp_file)?;
+        }
+        CompressionFormat::Zstd => {
+            let mut decoder = StreamingDecoder::new(probe).map_err(|e| CompressionError {
+                message: format!("Failed to initialize zstd decoder: {e}"),
+            })?;
+            io::copy(&mut decoder, &mut temp_file)?;
+        }
+        CompressionFormat::None => unreachable!(),
+    }
+
+    Ok(JavaHelperInput {
+        path: temp_path,
+        is_temp_file: true,
+    })
+}
+
 pub fn slurp_file(
     file_path: String,
     debug_mode: bool,
@@ -59,31 +256,126 @@ pub fn slurp_file(
 ) -> Result<RenderedResult, HprofSlurpError> {
     let file = File::open(&file_path)?;
     let file_len = file.metadata()?.len() as usize;
-    let mut reader = BufReader::new(file);
+    let reader = BufReader::new(file);
+    slurp_reader(
+        reader,
+        Some(file_len),
+        Some(&file_path),
+        debug_mode,
+        list_strings,
+    )
+}
+
+/// Analyzes a HotSpot (OpenJDK/Oracle) core file via the JDK's bundled Serviceability Agent.
+/// Unlike `slurp_file`, this isn't reached through `detect_dump_format`'s magic-byte sniffing —
+/// SA needs the exact `java` executable that produced the core, something no amount of
+/// byte-sniffing the core itself can recover — so a caller who knows they're holding a HotSpot
+/// core calls this directly.
+pub fn slurp_hotspot_core(
+    executable_path: &str,
+    core_path: &str,
+    list_strings: bool,
+) -> Result<RenderedResult, HprofSlurpError> {
+    let file_len = File::open(core_path)?.metadata()?.len();
+    analyze_hotspot_core_with_java_helper(executable_path, core_path, file_len, list_strings)
+}
+
+/// Analyzes a live HotSpot process by pid via the Serviceability Agent. There's no dump file to
+/// sniff a format from here, so — like [`slurp_hotspot_core`] — this is a dedicated entry point
+/// rather than something `detect_dump_format` could ever route to.
+pub fn slurp_hotspot_pid(pid: u32, list_strings: bool) -> Result<RenderedResult, HprofSlurpError> {
+    analyze_hotspot_pid_with_java_helper(pid, list_strings)
+}
+
+/// Generalized entry point: slurps a heap dump out of any `BufRead + Send` source, not just a
+/// `File`. `total_len` should be the source's known byte length (e.g. `file.metadata()?.len()`)
+/// or `None` when it can't be known upfront, such as stdin or a network socket. `source_path` is
+/// only used by the Java helper, which needs an on-disk path: for OpenJ9 core dumps, and for PHD
+/// dumps the native parser reports as `UnsupportedPhdFeature` (or every PHD dump when the
+/// `force-java-phd-helper` feature is on); passing `None` there is fine for plain hprof streams.
+pub fn slurp_reader<R: BufRead + Send + 'static>(
+    source: R,
+    total_len: Option<usize>,
+    source_path: Option<&str>,
+    debug_mode: bool,
+    list_strings: bool,
+) -> Result<RenderedResult, HprofSlurpError> {
+    let compressed_bytes_read = Arc::new(AtomicU64::new(0));
+    let (mut reader, known_stream_len, known_compressed_len) =
+        open_dump_reader(source, total_len, Arc::clone(&compressed_bytes_read))?;
 
     let probe_buffer = reader.fill_buf()?;
     if probe_buffer.is_empty() {
         return Err(UnsupportedDumpFormat {
-            message: "Empty input file".to_string(),
+            message: "Empty input stream".to_string(),
         });
     }
 
     match detect_dump_format(probe_buffer)? {
-        DumpFormat::Hprof => slurp_hprof(reader, file_len, debug_mode, list_strings),
-        DumpFormat::Phd => {
-            drop(reader);
-            analyze_with_java_helper("phd", &file_path, file_len as u64, list_strings)
+        DumpFormat::Hprof => slurp_hprof(
+            reader,
+            known_stream_len,
+            known_compressed_len,
+            total_len.unwrap_or(0),
+            compressed_bytes_read,
+            debug_mode,
+            list_strings,
+        ),
+        DumpFormat::Phd if cfg!(not(feature = "force-java-phd-helper")) => {
+            match crate::parser::phd_parser::parse_phd_dump(reader, list_strings) {
+                Ok(result) => Ok(result),
+                // The native parser only falls back for a record tag or format version it
+                // doesn't understand yet (`UnsupportedPhdFeature`) — never for a genuinely
+                // corrupt/truncated dump, which should surface its real parse error instead of
+                // being retried through the Java helper (and, on a machine with no JDK, reported
+                // as a confusing "can't locate a JDK" error instead).
+                Err(native_err @ UnsupportedPhdFeature { .. }) => {
+                    let Some(dump_path) = source_path else {
+                        return Err(native_err);
+                    };
+                    let helper_input = prepare_java_helper_input(dump_path)?;
+                    analyze_with_java_helper(
+                        "phd",
+                        helper_input.path_str()?,
+                        total_len.unwrap_or(0) as u64,
+                        list_strings,
+                    )
+                }
+                Err(native_err) => Err(native_err),
+            }
         }
-        DumpFormat::OpenJ9Core => {
+        format @ (DumpFormat::Phd | DumpFormat::OpenJ9Core) => {
+            let Some(dump_path) = source_path else {
+                return Err(UnsupportedDumpFormat {
+                    message:
+                        "PHD and OpenJ9 core dumps require an on-disk path for the Java helper; \
+                         streaming them from a non-file source is not supported yet"
+                            .to_string(),
+                });
+            };
             drop(reader);
-            analyze_with_java_helper("openj9-core", &file_path, file_len as u64, list_strings)
+            let format_label = match format {
+                DumpFormat::Phd => "phd",
+                DumpFormat::OpenJ9Core => "openj9-core",
+                DumpFormat::Hprof => unreachable!(),
+            };
+            let helper_input = prepare_java_helper_input(dump_path)?;
+            analyze_with_java_helper(
+                format_label,
+                helper_input.path_str()?,
+                total_len.unwrap_or(0) as u64,
+                list_strings,
+            )
         }
     }
 }
 
-fn slurp_hprof(
-    mut reader: BufReader<File>,
-    file_len: usize,
+fn slurp_hprof<R: BufRead + Send + 'static>(
+    mut reader: DumpReader<R>,
+    known_stream_len: Option<usize>,
+    known_compressed_len: Option<usize>,
+    input_len_hint: usize,
+    compressed_bytes_read: Arc<AtomicU64>,
     debug_mode: bool,
     list_strings: bool,
 ) -> Result<RenderedResult, HprofSlurpError> {
@@ -91,7 +383,14 @@ fn slurp_hprof(
     let id_size = header.size_pointers;
     println!(
         "Processing {} binary hprof file in '{}' format.",
-        pretty_bytes_size(file_len as u64),
+        match known_stream_len {
+            Some(len) => pretty_bytes_size(len as u64),
+            None if input_len_hint > 0 => format!(
+                "a compressed ({})",
+                pretty_bytes_size(input_len_hint as u64)
+            ),
+            None => "an unknown-length".to_string(),
+        },
         header.format
     );
 
@@ -127,7 +426,12 @@ fn slurp_hprof(
         crossbeam_channel::unbounded();
 
     // Init pre-fetcher
-    let prefetcher = PrefetchReader::new(reader, file_len, FILE_HEADER_LENGTH, READ_BUFFER_SIZE);
+    let prefetcher = PrefetchReader::new(
+        reader,
+        known_stream_len,
+        FILE_HEADER_LENGTH,
+        READ_BUFFER_SIZE,
+    );
     let prefetch_thread = prefetcher.start(send_data, receive_pooled_data)?;
 
     // Init pooled result vec
@@ -139,7 +443,7 @@ fn slurp_hprof(
     let initial_loop_buffer = Vec::with_capacity(READ_BUFFER_SIZE); // will be added to the data pool after the first chunk
     let stream_parser = HprofRecordStreamParser::new(
         debug_mode,
-        file_len,
+        id_size,
         FILE_HEADER_LENGTH,
         initial_loop_buffer,
     );
@@ -157,18 +461,48 @@ fn slurp_hprof(
     let result_recorder = ResultRecorder::new(id_size, list_strings);
     let recorder_thread = result_recorder.start(receive_records, send_result, send_pooled_vec)?;
 
-    // Init progress bar
-    let pb = ProgressBar::new(file_len as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} (speed:{bytes_per_sec}) (eta:{eta})")
-            .expect("templating should never fail")
-            .progress_chars("#>-"),
-    );
+    // Init progress bar. The parser-driven `receive_progress` channel reports decompressed
+    // bytes, which is only meaningful for an ETA when the decompressed length is known
+    // (`known_stream_len`). A compressed source's decompressed length never is, but its
+    // compressed length usually still is (e.g. from `File::metadata`) — in that case a bounded
+    // bar driven off `compressed_bytes_read` against `known_compressed_len` is just as good.
+    // Only a source whose length can't be known at all (stdin, a socket, ...) falls back to a
+    // spinner.
+    let pb = match (known_stream_len, known_compressed_len) {
+        (Some(total), _) | (None, Some(total)) => {
+            let pb = ProgressBar::new(total as u64);
+            pb.set_style(
+                ProgressStyle::default_bar()
+                    .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} (speed:{bytes_per_sec}) (eta:{eta})")
+                    .expect("templating should never fail")
+                    .progress_chars("#>-"),
+            );
+            pb
+        }
+        (None, None) => {
+            let pb = ProgressBar::new_spinner();
+            pb.set_style(
+                ProgressStyle::default_spinner()
+                    .template("[{elapsed_precise}] {spinner} {bytes} compressed bytes read (speed:{bytes_per_sec})")
+                    .expect("templating should never fail"),
+            );
+            pb
+        }
+    };
 
     // Feed progress bar
-    while let Ok(processed) = receive_progress.recv() {
-        pb.set_position(processed as u64);
+    if known_stream_len.is_some() {
+        while let Ok(processed) = receive_progress.recv() {
+            pb.set_position(processed as u64);
+        }
+    } else {
+        // Decompressed length unknown: drive the bar (bounded, if `known_compressed_len` is
+        // `Some`, otherwise the spinner) off the compressed bytes actually pulled off disk
+        // instead, until the parser reports it has drained the stream.
+        while let Ok(_processed) = receive_progress.recv() {
+            pb.set_position(compressed_bytes_read.load(Ordering::Relaxed));
+            pb.tick();
+        }
     }
 
     // Finish and remove progress bar
@@ -191,7 +525,7 @@ fn slurp_hprof(
     Ok(rendered_result)
 }
 
-pub fn slurp_header(reader: &mut BufReader<File>) -> Result<FileHeader, HprofSlurpError> {
+pub fn slurp_header<R: BufRead>(reader: &mut DumpReader<R>) -> Result<FileHeader, HprofSlurpError> {
     let mut header_buffer = vec![0; FILE_HEADER_LENGTH];
     reader.read_exact(&mut header_buffer)?;
     let (rest, header) = parse_file_header(&header_buffer).map_err(|e| InvalidHprofFile {
@@ -270,6 +604,12 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn hotspot_core_missing_file_is_an_error() {
+        let result = slurp_hotspot_core("java", "test-heap-dumps/does-not-exist.core", false);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn unsupported_32_bits() {
         let file_path = FILE_PATH_32.to_string();